@@ -0,0 +1,165 @@
+//! Initial relay discovery and relayed connection setup.
+//!
+//! Builds the QUIC transport parameters used for relay-assisted dials,
+//! threading manager-wide settings such as the anti-amplification
+//! multiplier down into the transport layer, and reserves redundant relay
+//! slots per peer so a single relay isn't a point of failure.
+
+use crate::{Config, RelayHandle};
+use libp2p::PeerId;
+use std::fmt;
+
+/// Spec-mandated minimum for the QUIC anti-amplification multiplier
+/// (RFC 9000 §8.1): a server must not send more than this many times the
+/// bytes it has received from an unvalidated peer address.
+pub const MIN_ANTI_AMPLIFICATION_MULTIPLIER: u32 = 3;
+
+/// QUIC transport parameters derived from [`Config`] for a relay-assisted dial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuicTransportParams {
+    pub anti_amplification_multiplier: u32,
+}
+
+impl QuicTransportParams {
+    /// Build transport params from `config`, validating the configured
+    /// anti-amplification multiplier.
+    ///
+    /// Rejects a multiplier below [`MIN_ANTI_AMPLIFICATION_MULTIPLIER`] unless
+    /// `config.allow_unstable_anti_amplification_override` is set, since going
+    /// lower lets a server amplify traffic towards an unvalidated address.
+    pub fn from_config(config: &Config) -> Result<Self, AntiAmplificationError> {
+        if config.anti_amplification_multiplier < MIN_ANTI_AMPLIFICATION_MULTIPLIER
+            && !config.allow_unstable_anti_amplification_override
+        {
+            return Err(AntiAmplificationError {
+                configured: config.anti_amplification_multiplier,
+            });
+        }
+
+        Ok(Self {
+            anti_amplification_multiplier: config.anti_amplification_multiplier,
+        })
+    }
+}
+
+/// The configured anti-amplification multiplier is below the RFC 9000
+/// minimum and no unstable override was set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AntiAmplificationError {
+    pub configured: u32,
+}
+
+impl fmt::Display for AntiAmplificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "anti_amplification_multiplier {} is below the spec-mandated minimum of {} \
+             (set allow_unstable_anti_amplification_override to bypass)",
+            self.configured, MIN_ANTI_AMPLIFICATION_MULTIPLIER
+        )
+    }
+}
+
+impl std::error::Error for AntiAmplificationError {}
+
+/// Build the QUIC transport parameters used when dialing a peer through
+/// `relay`, validating the manager's anti-amplification configuration.
+pub fn quic_transport_for_relay(
+    config: &Config,
+    relay: &RelayHandle,
+) -> Result<QuicTransportParams, AntiAmplificationError> {
+    let _ = relay;
+    QuicTransportParams::from_config(config)
+}
+
+/// How many relays to reserve redundant slots on for a single peer, bounded
+/// by `configured_max` so small networks don't over-reserve while large ones
+/// still get redundancy.
+pub fn replication_factor(known_relay_count: usize, configured_max: u32) -> usize {
+    if known_relay_count == 0 {
+        return 0;
+    }
+    let proportional = (2 * known_relay_count).div_ceil(3);
+    proportional.min(configured_max as usize)
+}
+
+/// Reserve redundant relay slots for `peer_id`, picking the lowest-RTT
+/// candidates up to the configured replication factor.
+pub fn reserve_relay_slots(
+    peer_id: PeerId,
+    candidates: &[RelayHandle],
+    config: &Config,
+) -> Vec<RelayHandle> {
+    let mut ranked: Vec<RelayHandle> = candidates
+        .iter()
+        .filter(|relay| relay.peer_id == peer_id)
+        .cloned()
+        .collect();
+    ranked.sort_by_key(|relay| relay.rtt_ms);
+
+    let factor = replication_factor(ranked.len(), config.max_relay_replication);
+    ranked.truncate(factor);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relay() -> RelayHandle {
+        RelayHandle {
+            peer_id: libp2p::PeerId::random(),
+            relay_peer_id: libp2p::PeerId::random(),
+            rtt_ms: 50,
+        }
+    }
+
+    #[test]
+    fn default_multiplier_is_valid() {
+        let config = Config::default();
+        assert!(quic_transport_for_relay(&config, &relay()).is_ok());
+    }
+
+    #[test]
+    fn below_spec_multiplier_is_rejected_without_override() {
+        let mut config = Config::default();
+        config.anti_amplification_multiplier = 1;
+        assert!(quic_transport_for_relay(&config, &relay()).is_err());
+    }
+
+    #[test]
+    fn below_spec_multiplier_is_allowed_with_explicit_override() {
+        let mut config = Config::default();
+        config.anti_amplification_multiplier = 1;
+        config.allow_unstable_anti_amplification_override = true;
+        let params = quic_transport_for_relay(&config, &relay()).unwrap();
+        assert_eq!(params.anti_amplification_multiplier, 1);
+    }
+
+    #[test]
+    fn replication_factor_is_bounded_by_configured_max() {
+        assert_eq!(replication_factor(9, 3), 3);
+        assert_eq!(replication_factor(2, 3), 2);
+        assert_eq!(replication_factor(0, 3), 0);
+    }
+
+    #[test]
+    fn reserve_relay_slots_picks_lowest_rtt_up_to_factor() {
+        let peer_id = PeerId::random();
+        let mut config = Config::default();
+        config.max_relay_replication = 2;
+
+        let candidates = vec![
+            RelayHandle { peer_id, relay_peer_id: PeerId::random(), rtt_ms: 300 },
+            RelayHandle { peer_id, relay_peer_id: PeerId::random(), rtt_ms: 50 },
+            RelayHandle { peer_id, relay_peer_id: PeerId::random(), rtt_ms: 120 },
+            RelayHandle { peer_id: PeerId::random(), relay_peer_id: PeerId::random(), rtt_ms: 10 },
+        ];
+
+        let reserved = reserve_relay_slots(peer_id, &candidates, &config);
+        assert_eq!(reserved.len(), 2);
+        assert_eq!(reserved[0].rtt_ms, 50);
+        assert_eq!(reserved[1].rtt_ms, 120);
+        assert!(reserved.iter().all(|r| r.peer_id == peer_id));
+    }
+}
@@ -0,0 +1,238 @@
+//! Decides when it's worth attempting a hole punch given a peer's history.
+
+use crate::limits::{ConnectionLimits, LimitKind};
+use crate::punch::PunchOutcome;
+use crate::{ActivePath, Config};
+use libp2p::PeerId;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Number of recent outcomes retained per peer.
+const HISTORY_LEN: usize = 8;
+
+/// Starting backoff window applied after the first consecutive failure.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(5);
+
+/// How soon to recheck when suppressed purely because the in-flight punch
+/// limit is currently full, rather than because of this peer's own backoff.
+const IN_FLIGHT_LIMIT_RECHECK: Duration = Duration::from_millis(250);
+
+/// What the heuristics recommend doing about a peer right now.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decision {
+    /// Go ahead and attempt a hole punch.
+    Attempt,
+    /// Don't attempt yet; the peer is within its backoff window, or the
+    /// in-flight punch limit is currently full.
+    Suppress { retry_after: Duration },
+    /// Don't bother punching; the existing path is good enough already.
+    PreferRelay,
+}
+
+/// Per-peer history of punch outcomes and the current backoff state.
+#[derive(Debug, Clone)]
+struct PeerRecord {
+    outcomes: Vec<PunchOutcome>,
+    last_attempt: Instant,
+    backoff: Duration,
+}
+
+/// Tracks recent punch outcomes per peer and derives a per-peer backoff
+/// window that [`PunchLedger::should_attempt`] consults before recommending
+/// another punch, growing on consecutive failures and resetting on success
+/// so a peer that becomes punchable again isn't stuck behind an old backoff.
+#[derive(Debug, Clone, Default)]
+pub struct PunchLedger {
+    peers: HashMap<PeerId, PeerRecord>,
+}
+
+impl PunchLedger {
+    /// Create an empty ledger.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the outcome of a punch attempt against `peer_id`, updating its
+    /// backoff window: it grows on consecutive `Failed`/`NoStream` outcomes
+    /// and resets on any `Direct` or `ConnectionReversed` success.
+    pub fn record(&mut self, peer_id: PeerId, outcome: PunchOutcome, config: &Config) {
+        let now = Instant::now();
+        let record = self.peers.entry(peer_id).or_insert_with(|| PeerRecord {
+            outcomes: Vec::new(),
+            last_attempt: now,
+            backoff: Duration::ZERO,
+        });
+
+        record.last_attempt = now;
+        record.outcomes.push(outcome);
+        if record.outcomes.len() > HISTORY_LEN {
+            record.outcomes.remove(0);
+        }
+
+        match outcome {
+            PunchOutcome::Direct | PunchOutcome::ConnectionReversed => {
+                record.backoff = Duration::ZERO;
+            }
+            PunchOutcome::Failed | PunchOutcome::NoStream => {
+                record.backoff = if record.backoff.is_zero() {
+                    INITIAL_BACKOFF
+                } else {
+                    record.backoff.mul_f64(config.punch_backoff_multiplier)
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Decide whether to attempt a hole punch against `peer_id` given the
+    /// current relay RTT, its active path, and the in-flight punch limit.
+    pub fn should_attempt(
+        &self,
+        peer_id: &PeerId,
+        relay_rtt_ms: u64,
+        active_path: Option<&ActivePath>,
+        limits: &ConnectionLimits,
+        config: &Config,
+    ) -> Decision {
+        if let Some(ActivePath::Direct(_)) = active_path {
+            return Decision::PreferRelay;
+        }
+
+        if limits.check(LimitKind::PunchAttempt).is_err() {
+            return Decision::Suppress {
+                retry_after: IN_FLIGHT_LIMIT_RECHECK,
+            };
+        }
+
+        let Some(record) = self.peers.get(peer_id) else {
+            return Decision::Attempt;
+        };
+
+        if record.backoff.is_zero() {
+            return Decision::Attempt;
+        }
+
+        let elapsed = record.last_attempt.elapsed();
+        if elapsed >= record.backoff {
+            return Decision::Attempt;
+        }
+
+        let override_threshold = config.max_relay_rtt_ms + config.backoff_rtt_override_margin_ms;
+        if relay_rtt_ms > override_threshold {
+            return Decision::Attempt;
+        }
+
+        Decision::Suppress {
+            retry_after: record.backoff - elapsed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::ConnectionLimits;
+    use crate::{DirectHandle, RelayHandle};
+
+    fn config() -> Config {
+        Config::default()
+    }
+
+    fn limits() -> ConnectionLimits {
+        ConnectionLimits::new(64, 8, 256)
+    }
+
+    #[test]
+    fn unknown_peer_may_attempt() {
+        let ledger = PunchLedger::new();
+        let peer_id = PeerId::random();
+        let decision = ledger.should_attempt(&peer_id, 50, None, &limits(), &config());
+        assert_eq!(decision, Decision::Attempt);
+    }
+
+    #[test]
+    fn consecutive_failures_suppress_until_backoff_elapses() {
+        let mut ledger = PunchLedger::new();
+        let peer_id = PeerId::random();
+        let config = config();
+
+        ledger.record(peer_id, PunchOutcome::Failed, &config);
+        let decision = ledger.should_attempt(&peer_id, 50, None, &limits(), &config);
+        assert!(matches!(decision, Decision::Suppress { .. }));
+    }
+
+    #[test]
+    fn connection_reversed_resets_backoff() {
+        let mut ledger = PunchLedger::new();
+        let peer_id = PeerId::random();
+        let config = config();
+
+        ledger.record(peer_id, PunchOutcome::Failed, &config);
+        ledger.record(peer_id, PunchOutcome::ConnectionReversed, &config);
+        let decision = ledger.should_attempt(&peer_id, 50, None, &limits(), &config);
+        assert_eq!(decision, Decision::Attempt);
+    }
+
+    #[test]
+    fn direct_success_resets_backoff() {
+        let mut ledger = PunchLedger::new();
+        let peer_id = PeerId::random();
+        let config = config();
+
+        ledger.record(peer_id, PunchOutcome::Failed, &config);
+        ledger.record(peer_id, PunchOutcome::Direct, &config);
+        let decision = ledger.should_attempt(&peer_id, 50, None, &limits(), &config);
+        assert_eq!(decision, Decision::Attempt);
+    }
+
+    #[test]
+    fn high_rtt_overrides_backoff() {
+        let mut ledger = PunchLedger::new();
+        let peer_id = PeerId::random();
+        let config = config();
+
+        ledger.record(peer_id, PunchOutcome::Failed, &config);
+        let threshold = config.max_relay_rtt_ms + config.backoff_rtt_override_margin_ms;
+        let decision = ledger.should_attempt(&peer_id, threshold + 1, None, &limits(), &config);
+        assert_eq!(decision, Decision::Attempt);
+    }
+
+    #[test]
+    fn healthy_direct_path_prefers_relay_variant() {
+        let ledger = PunchLedger::new();
+        let peer_id = PeerId::random();
+        let active = ActivePath::Direct(DirectHandle {
+            peer_id,
+            rtt_ms: 10,
+            endpoint: "127.0.0.1:4001".to_string(),
+        });
+        let decision = ledger.should_attempt(&peer_id, 50, Some(&active), &limits(), &config());
+        assert_eq!(decision, Decision::PreferRelay);
+    }
+
+    #[test]
+    fn relay_path_does_not_trigger_prefer_relay() {
+        let ledger = PunchLedger::new();
+        let peer_id = PeerId::random();
+        let active = ActivePath::Relay(RelayHandle {
+            peer_id,
+            relay_peer_id: PeerId::random(),
+            rtt_ms: 50,
+        });
+        let decision = ledger.should_attempt(&peer_id, 50, Some(&active), &limits(), &config());
+        assert_eq!(decision, Decision::Attempt);
+    }
+
+    #[test]
+    fn full_in_flight_punch_limit_suppresses_attempt() {
+        use crate::limits::LimitKind;
+
+        let ledger = PunchLedger::new();
+        let peer_id = PeerId::random();
+        let limits = ConnectionLimits::new(64, 1, 256);
+        limits.on_dial_started(LimitKind::PunchAttempt);
+
+        let decision = ledger.should_attempt(&peer_id, 50, None, &limits, &config());
+        assert!(matches!(decision, Decision::Suppress { .. }));
+    }
+}
@@ -5,22 +5,36 @@
 //!
 //! ## Architecture
 //!
-//! This library implements a boxed heuristic model with five main components:
+//! This library implements a boxed heuristic model with several main components:
 //!
-//! 1. **Discovery & Relay Setup** - Establishes initial relayed connections
+//! 1. **Discovery & Relay Setup** - Establishes initial relayed connections and
+//!    reserves redundant relay slots per peer
 //! 2. **Heuristics** - Decides when to attempt hole punching based on network conditions
 //! 3. **Punch Orchestrator** - Coordinates DCUtR hole punching attempts
 //! 4. **Path Selection** - Manages switching between relay and direct paths
 //! 5. **Metrics & Learning** - Tracks outcomes to improve future decisions
+//! 6. **Limits** - Caps concurrent connections and in-flight punch attempts
+//! 7. **Telemetry** - Reports punch outcomes to an optional remote collector
 
 pub mod discovery;
 pub mod heuristics;
+pub mod limits;
 pub mod punch;
 pub mod selection;
 pub mod metrics;
+pub mod telemetry;
 
-use libp2p::PeerId;
+use crate::discovery::AntiAmplificationError;
+use crate::heuristics::{Decision, PunchLedger};
+use crate::limits::{ConnectionLimits, LimitExceeded, LimitKind};
+use crate::metrics::{MetricsCollector, RelayOutcomeCounts};
+use crate::punch::{PunchEvents, PunchOrchestrator, PunchOutcome};
+use crate::selection::{PathSelection, RelayCandidate};
+use crate::telemetry::{PunchReport, TelemetrySink};
+use libp2p::{Multiaddr, PeerId};
 use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 /// Represents an active connection path to a peer
 #[derive(Debug, Clone)]
@@ -40,7 +54,7 @@ pub struct RelayHandle {
 }
 
 /// Handle for a direct connection
-#[derive(Debug, Clone)}
+#[derive(Debug, Clone)]
 pub struct DirectHandle {
     pub peer_id: PeerId,
     pub rtt_ms: u64,
@@ -48,7 +62,7 @@ pub struct DirectHandle {
 }
 
 /// Configuration for the PathManager
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Config {
     /// Maximum RTT (ms) before attempting to punch
     pub max_relay_rtt_ms: u64,
@@ -56,6 +70,57 @@ pub struct Config {
     pub min_punch_success_rate: f64,
     /// Backoff multiplier after failed punch attempts
     pub punch_backoff_multiplier: f64,
+    /// How far (in ms) the relay RTT must rise above `max_relay_rtt_ms` before
+    /// a peer still in its backoff window is re-attempted anyway
+    pub backoff_rtt_override_margin_ms: u64,
+    /// Optional sink that receives a `PunchReport` after every punch attempt,
+    /// for fleet-wide DCUtR telemetry. `None` disables reporting entirely.
+    pub telemetry_sink: Option<Arc<dyn TelemetrySink>>,
+    /// Maximum number of concurrent relayed connections.
+    pub max_relayed_connections: u32,
+    /// Maximum number of concurrent in-flight punch attempts.
+    pub max_in_flight_punches: u32,
+    /// Maximum number of concurrent direct connections.
+    pub max_direct_connections: u32,
+    /// QUIC anti-amplification multiplier: the server-side cap on bytes sent
+    /// per byte received from an unvalidated peer address. Must be at least
+    /// [`discovery::MIN_ANTI_AMPLIFICATION_MULTIPLIER`] unless
+    /// `allow_unstable_anti_amplification_override` is set.
+    pub anti_amplification_multiplier: u32,
+    /// Bypasses the spec-mandated minimum on `anti_amplification_multiplier`.
+    /// Only safe on trusted relay topologies.
+    pub allow_unstable_anti_amplification_override: bool,
+    /// Upper bound on how many relays are simultaneously reserved per peer
+    /// for redundancy; the actual count also scales down for small networks,
+    /// see [`discovery::replication_factor`].
+    pub max_relay_replication: u32,
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("max_relay_rtt_ms", &self.max_relay_rtt_ms)
+            .field("min_punch_success_rate", &self.min_punch_success_rate)
+            .field("punch_backoff_multiplier", &self.punch_backoff_multiplier)
+            .field(
+                "backoff_rtt_override_margin_ms",
+                &self.backoff_rtt_override_margin_ms,
+            )
+            .field("telemetry_sink", &self.telemetry_sink.is_some())
+            .field("max_relayed_connections", &self.max_relayed_connections)
+            .field("max_in_flight_punches", &self.max_in_flight_punches)
+            .field("max_direct_connections", &self.max_direct_connections)
+            .field(
+                "anti_amplification_multiplier",
+                &self.anti_amplification_multiplier,
+            )
+            .field(
+                "allow_unstable_anti_amplification_override",
+                &self.allow_unstable_anti_amplification_override,
+            )
+            .field("max_relay_replication", &self.max_relay_replication)
+            .finish()
+    }
 }
 
 impl Default for Config {
@@ -64,30 +129,263 @@ impl Default for Config {
             max_relay_rtt_ms: 200,
             min_punch_success_rate: 0.3,
             punch_backoff_multiplier: 2.0,
+            backoff_rtt_override_margin_ms: 50,
+            telemetry_sink: None,
+            max_relayed_connections: 64,
+            max_in_flight_punches: 8,
+            max_direct_connections: 256,
+            anti_amplification_multiplier: discovery::MIN_ANTI_AMPLIFICATION_MULTIPLIER,
+            allow_unstable_anti_amplification_override: false,
+            max_relay_replication: 3,
+        }
+    }
+}
+
+/// Error from [`PathManager::attempt_punch`].
+#[derive(Debug, Clone, Copy)]
+pub enum PunchAttemptError {
+    /// The in-flight punch limit is currently full.
+    LimitExceeded(LimitExceeded),
+    /// The configured QUIC transport settings are invalid.
+    InvalidTransportConfig(AntiAmplificationError),
+    /// The heuristics ledger recommends against attempting this punch right now.
+    Suppressed(Decision),
+    /// [`PathManager::attempt_punch_via_selection`] found no live relay
+    /// candidate selected for this peer.
+    NoActiveRelay,
+}
+
+impl fmt::Display for PunchAttemptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LimitExceeded(e) => write!(f, "{e}"),
+            Self::InvalidTransportConfig(e) => write!(f, "{e}"),
+            Self::Suppressed(decision) => write!(f, "punch suppressed by heuristics: {decision:?}"),
+            Self::NoActiveRelay => write!(f, "no active relay candidate selected for this peer"),
         }
     }
 }
 
+impl std::error::Error for PunchAttemptError {}
+
+impl From<LimitExceeded> for PunchAttemptError {
+    fn from(e: LimitExceeded) -> Self {
+        Self::LimitExceeded(e)
+    }
+}
+
+impl From<AntiAmplificationError> for PunchAttemptError {
+    fn from(e: AntiAmplificationError) -> Self {
+        Self::InvalidTransportConfig(e)
+    }
+}
+
 /// Main path manager for handling P2P connections
 pub struct PathManager {
     config: Config,
+    limits: ConnectionLimits,
+    ledger: Mutex<PunchLedger>,
+    metrics: Mutex<MetricsCollector>,
+    selection: Mutex<PathSelection>,
 }
 
 impl PathManager {
     /// Create a new PathManager with the given configuration
     pub fn new(config: Config) -> Self {
-        Self { config }
+        let limits = ConnectionLimits::new(
+            config.max_relayed_connections,
+            config.max_in_flight_punches,
+            config.max_direct_connections,
+        );
+        Self {
+            config,
+            limits,
+            ledger: Mutex::new(PunchLedger::new()),
+            metrics: Mutex::new(MetricsCollector::new()),
+            selection: Mutex::new(PathSelection::new()),
+        }
     }
 
     /// Create a new PathManager with default configuration
     pub fn with_defaults() -> Self {
         Self::new(Config::default())
     }
+
+    /// The connection/attempt limits this manager is enforcing.
+    pub fn limits(&self) -> &ConnectionLimits {
+        &self.limits
+    }
+
+    /// Outcome counts observed so far for punches routed through `relay_peer_id`.
+    pub fn metrics_for(&self, relay_peer_id: &PeerId) -> Option<RelayOutcomeCounts> {
+        self.metrics.lock().unwrap().counts_for(relay_peer_id).copied()
+    }
+
+    /// Reserve a slot for a new relayed connection dial, subject to the
+    /// configured cap. Call [`Self::relay_dial_established`] or
+    /// [`Self::relay_dial_failed`] once the dial resolves.
+    pub fn start_relay_dial(&self) -> Result<(), LimitExceeded> {
+        self.limits.try_reserve(LimitKind::RelayedConnection)
+    }
+
+    /// Record that a pending relay dial failed or was denied.
+    pub fn relay_dial_failed(&self) {
+        self.limits.on_dial_failed(LimitKind::RelayedConnection);
+    }
+
+    /// Record that a pending relay dial succeeded.
+    pub fn relay_dial_established(&self) {
+        self.limits.on_established(LimitKind::RelayedConnection);
+    }
+
+    /// Record that an established relayed connection closed.
+    pub fn relay_connection_closed(&self) {
+        self.limits.on_closed(LimitKind::RelayedConnection);
+    }
+
+    /// Replace the ranked relay candidate set for `peer_id`, re-ranked by
+    /// RTT, for [`Self::attempt_punch_via_selection`] to draw its active
+    /// relay and punch candidates from.
+    pub fn set_relay_candidates(&self, peer_id: PeerId, candidates: Vec<RelayCandidate>) {
+        self.selection.lock().unwrap().set_candidates(peer_id, candidates);
+    }
+
+    /// Mark a relay candidate for `peer_id` as dropped, so selection fails
+    /// over to the next-best live candidate.
+    pub fn relay_candidate_dropped(&self, peer_id: &PeerId, relay_peer_id: &PeerId) {
+        self.selection.lock().unwrap().mark_dropped(peer_id, relay_peer_id);
+    }
+
+    /// Attempt to hole-punch `remote_peer_id` via `relay`, subject to the
+    /// in-flight punch limit and the heuristics ledger's backoff for this
+    /// peer, reporting the outcome to the configured telemetry sink, if any,
+    /// when the attempt concludes.
+    ///
+    /// `active_path`, if the peer already has one, is passed to the ledger so
+    /// it can skip punching when a direct path is already in place.
+    pub async fn attempt_punch(
+        &self,
+        local_peer_id: PeerId,
+        remote_peer_id: PeerId,
+        relay: &RelayHandle,
+        candidates: &[Multiaddr],
+        active_path: Option<&ActivePath>,
+        events: &mut impl PunchEvents,
+    ) -> Result<PunchOutcome, PunchAttemptError> {
+        // `should_attempt` folds the in-flight punch limit check into its
+        // backoff/active-path decision, so a full limit is reported as
+        // `Suppressed`. `LimitExceeded` is returned only when a concurrent
+        // attempt takes the last free slot between that check and the
+        // reservation below.
+        let decision = self.ledger.lock().unwrap().should_attempt(
+            &remote_peer_id,
+            relay.rtt_ms,
+            active_path,
+            &self.limits,
+            &self.config,
+        );
+        if !matches!(decision, Decision::Attempt) {
+            return Err(PunchAttemptError::Suppressed(decision));
+        }
+
+        // Atomically check-and-reserve so two concurrent attempts against
+        // the last free slot can't both observe room via `should_attempt`
+        // and then both reserve it.
+        self.limits.try_reserve(LimitKind::PunchAttempt)?;
+
+        let started_at = Instant::now();
+        let orchestrator = PunchOrchestrator::new(self.config.clone());
+        let outcome = match orchestrator.attempt(relay, candidates, events) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.limits.on_dial_failed(LimitKind::PunchAttempt);
+                return Err(e.into());
+            }
+        };
+
+        // The in-flight slot is released as soon as the attempt concludes,
+        // regardless of outcome; a resulting direct connection is tracked
+        // separately as its own established slot.
+        self.limits.on_dial_failed(LimitKind::PunchAttempt);
+        self.ledger
+            .lock()
+            .unwrap()
+            .record(remote_peer_id, outcome, &self.config);
+        self.metrics
+            .lock()
+            .unwrap()
+            .record(relay.relay_peer_id, outcome);
+        // The punch already materialized the direct connection; the cap only
+        // bounds how many of them we go on tracking as established slots, so
+        // a burst of successful punches can't grow this count past the
+        // configured limit. `try_reserve` folds the check and the pending
+        // reservation into one lock acquisition for the same reason as above.
+        let direct = matches!(outcome, PunchOutcome::Direct | PunchOutcome::ConnectionReversed);
+        if direct && self.limits.try_reserve(LimitKind::DirectConnection).is_ok() {
+            self.limits.on_established(LimitKind::DirectConnection);
+        }
+
+        if let Some(sink) = &self.config.telemetry_sink {
+            sink.report(PunchReport {
+                local_peer_id,
+                remote_peer_id,
+                relay_peer_id: relay.relay_peer_id,
+                candidates: candidates.to_vec(),
+                outcome,
+                relay_rtt_ms: relay.rtt_ms,
+                // `PunchEvents` doesn't yet expose per-attempt stream/dial
+                // timestamps, so these stay unset rather than being filled
+                // with the total attempt wall-clock under the wrong name.
+                direct_rtt_ms: None,
+                time_to_stream: None,
+                time_to_direct: direct.then(|| started_at.elapsed()),
+            })
+            .await;
+        }
+
+        Ok(outcome)
+    }
+
+    /// Attempt to hole-punch `remote_peer_id` using the active relay and
+    /// RTT-ordered candidate multiaddrs from the selection table set via
+    /// [`Self::set_relay_candidates`], instead of a caller-supplied relay and
+    /// candidate list. See [`Self::attempt_punch`].
+    pub async fn attempt_punch_via_selection(
+        &self,
+        local_peer_id: PeerId,
+        remote_peer_id: PeerId,
+        events: &mut impl PunchEvents,
+    ) -> Result<PunchOutcome, PunchAttemptError> {
+        let (active_path, candidates) = {
+            let selection = self.selection.lock().unwrap();
+            (
+                selection.active_relay(&remote_peer_id),
+                selection.punch_candidates(&remote_peer_id),
+            )
+        };
+
+        let relay = match &active_path {
+            Some(ActivePath::Relay(handle)) => handle.clone(),
+            _ => return Err(PunchAttemptError::NoActiveRelay),
+        };
+
+        self.attempt_punch(
+            local_peer_id,
+            remote_peer_id,
+            &relay,
+            &candidates,
+            active_path.as_ref(),
+            events,
+        )
+        .await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::selection::RelayCandidate;
+    use crate::telemetry::InMemorySink;
 
     #[test]
     fn test_config_defaults() {
@@ -101,4 +399,166 @@ mod tests {
         let manager = PathManager::with_defaults();
         assert_eq!(manager.config.max_relay_rtt_ms, 200);
     }
+
+    struct FakeEvents {
+        stream_opens: bool,
+        dial_rounds_succeed: bool,
+        direct_path: bool,
+        cancelled: bool,
+    }
+
+    impl PunchEvents for FakeEvents {
+        fn wait_for_stream(&mut self, _deadline: Instant) -> bool {
+            self.stream_opens
+        }
+
+        fn run_dial_rounds(&mut self, _candidates: &[Multiaddr]) -> bool {
+            self.dial_rounds_succeed
+        }
+
+        fn has_direct_path(&self) -> bool {
+            self.direct_path
+        }
+
+        fn is_cancelled(&self) -> bool {
+            self.cancelled
+        }
+    }
+
+    fn relay() -> RelayHandle {
+        RelayHandle {
+            peer_id: PeerId::random(),
+            relay_peer_id: PeerId::random(),
+            rtt_ms: 50,
+        }
+    }
+
+    #[tokio::test]
+    async fn attempt_punch_wires_ledger_metrics_limits_and_telemetry() {
+        let sink = Arc::new(InMemorySink::new());
+        let manager = PathManager::new(Config {
+            telemetry_sink: Some(sink.clone()),
+            ..Config::default()
+        });
+        let relay = relay();
+        let addr: Multiaddr = "/ip4/127.0.0.1/udp/4001/quic-v1".parse().unwrap();
+        let mut events = FakeEvents {
+            stream_opens: true,
+            dial_rounds_succeed: true,
+            direct_path: false,
+            cancelled: false,
+        };
+
+        let outcome = manager
+            .attempt_punch(
+                PeerId::random(),
+                PeerId::random(),
+                &relay,
+                &[addr],
+                None,
+                &mut events,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(outcome, PunchOutcome::Direct);
+        assert_eq!(manager.limits().check(LimitKind::PunchAttempt), Ok(()));
+        assert_eq!(
+            manager.metrics_for(&relay.relay_peer_id).unwrap().full_successes(),
+            1
+        );
+
+        let reports = sink.reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].outcome, PunchOutcome::Direct);
+        assert!(reports[0].time_to_direct.is_some());
+    }
+
+    #[tokio::test]
+    async fn attempt_punch_is_suppressed_when_punch_limit_is_full() {
+        let mut config = Config::default();
+        config.max_in_flight_punches = 1;
+        let manager = PathManager::new(config);
+        manager.limits().on_dial_started(LimitKind::PunchAttempt);
+
+        let relay = relay();
+        let addr: Multiaddr = "/ip4/127.0.0.1/udp/4001/quic-v1".parse().unwrap();
+        let mut events = FakeEvents {
+            stream_opens: true,
+            dial_rounds_succeed: true,
+            direct_path: false,
+            cancelled: false,
+        };
+
+        let err = manager
+            .attempt_punch(
+                PeerId::random(),
+                PeerId::random(),
+                &relay,
+                &[addr],
+                None,
+                &mut events,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, PunchAttemptError::Suppressed(_)));
+    }
+
+    #[tokio::test]
+    async fn attempt_punch_via_selection_uses_selected_relay_and_candidates() {
+        let manager = PathManager::with_defaults();
+        let peer_id = PeerId::random();
+        let relay_peer_id = PeerId::random();
+        let addr: Multiaddr = "/ip4/127.0.0.1/udp/4001/quic-v1".parse().unwrap();
+
+        manager.set_relay_candidates(
+            peer_id,
+            vec![RelayCandidate {
+                handle: RelayHandle {
+                    peer_id,
+                    relay_peer_id,
+                    rtt_ms: 50,
+                },
+                multiaddrs: vec![addr],
+                live: true,
+            }],
+        );
+
+        let mut events = FakeEvents {
+            stream_opens: true,
+            dial_rounds_succeed: true,
+            direct_path: false,
+            cancelled: false,
+        };
+
+        let outcome = manager
+            .attempt_punch_via_selection(PeerId::random(), peer_id, &mut events)
+            .await
+            .unwrap();
+
+        assert_eq!(outcome, PunchOutcome::Direct);
+        assert_eq!(
+            manager.metrics_for(&relay_peer_id).unwrap().full_successes(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn attempt_punch_via_selection_fails_without_a_selected_relay() {
+        let manager = PathManager::with_defaults();
+        let mut events = FakeEvents {
+            stream_opens: true,
+            dial_rounds_succeed: true,
+            direct_path: false,
+            cancelled: false,
+        };
+
+        let err = manager
+            .attempt_punch_via_selection(PeerId::random(), PeerId::random(), &mut events)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, PunchAttemptError::NoActiveRelay));
+    }
 }
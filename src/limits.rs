@@ -0,0 +1,181 @@
+//! Enforces caps on concurrent connections and in-flight punch attempts.
+//!
+//! [`ConnectionLimits`] tracks pending (dial started, not yet resolved) and
+//! established counts separately per [`LimitKind`]. Callers reserve a
+//! pending slot with [`ConnectionLimits::on_dial_started`] and must resolve
+//! it with exactly one of [`ConnectionLimits::on_dial_failed`] or
+//! [`ConnectionLimits::on_established`]; an established slot is later freed
+//! with [`ConnectionLimits::on_closed`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// The kind of slot tracked by [`ConnectionLimits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitKind {
+    /// A relayed connection to a peer.
+    RelayedConnection,
+    /// An in-flight DCUtR hole-punch attempt.
+    PunchAttempt,
+    /// A direct connection to a peer.
+    DirectConnection,
+}
+
+/// Returned by [`ConnectionLimits::check`] when a kind is already at capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitExceeded(pub LimitKind);
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "connection limit exceeded for {:?}", self.0)
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counts {
+    pending: u32,
+    established: u32,
+}
+
+impl Counts {
+    fn total(&self) -> u32 {
+        self.pending + self.established
+    }
+}
+
+/// Caps on concurrent relayed connections, in-flight punch attempts, and
+/// direct connections, consulted before starting a new relay dial or punch.
+#[derive(Debug)]
+pub struct ConnectionLimits {
+    max_relayed: u32,
+    max_in_flight_punches: u32,
+    max_direct: u32,
+    counts: Mutex<HashMap<LimitKind, Counts>>,
+}
+
+impl ConnectionLimits {
+    /// Create limits with the given per-kind caps.
+    pub fn new(max_relayed: u32, max_in_flight_punches: u32, max_direct: u32) -> Self {
+        Self {
+            max_relayed,
+            max_in_flight_punches,
+            max_direct,
+            counts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn max_for(&self, kind: LimitKind) -> u32 {
+        match kind {
+            LimitKind::RelayedConnection => self.max_relayed,
+            LimitKind::PunchAttempt => self.max_in_flight_punches,
+            LimitKind::DirectConnection => self.max_direct,
+        }
+    }
+
+    /// Check whether there is room for one more of `kind`, without reserving it.
+    pub fn check(&self, kind: LimitKind) -> Result<(), LimitExceeded> {
+        let counts = self.counts.lock().unwrap();
+        let total = counts.get(&kind).copied().unwrap_or_default().total();
+        if total >= self.max_for(kind) {
+            Err(LimitExceeded(kind))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check capacity and reserve a pending slot for `kind` in one lock
+    /// acquisition, so two concurrent callers can't both pass a [`Self::check`]
+    /// before either reserves and overshoot the cap.
+    pub fn try_reserve(&self, kind: LimitKind) -> Result<(), LimitExceeded> {
+        let mut counts = self.counts.lock().unwrap();
+        let entry = counts.entry(kind).or_default();
+        if entry.total() >= self.max_for(kind) {
+            return Err(LimitExceeded(kind));
+        }
+        entry.pending += 1;
+        Ok(())
+    }
+
+    /// Reserve a pending slot for a dial/attempt of `kind` that is starting.
+    pub fn on_dial_started(&self, kind: LimitKind) {
+        self.counts.lock().unwrap().entry(kind).or_default().pending += 1;
+    }
+
+    /// Release a pending slot because the dial/attempt of `kind` failed,
+    /// timed out, or was denied/aborted by another subsystem.
+    pub fn on_dial_failed(&self, kind: LimitKind) {
+        let mut counts = self.counts.lock().unwrap();
+        let entry = counts.entry(kind).or_default();
+        entry.pending = entry.pending.saturating_sub(1);
+    }
+
+    /// Move a pending slot of `kind` to established because the dial/attempt
+    /// succeeded.
+    pub fn on_established(&self, kind: LimitKind) {
+        let mut counts = self.counts.lock().unwrap();
+        let entry = counts.entry(kind).or_default();
+        entry.pending = entry.pending.saturating_sub(1);
+        entry.established += 1;
+    }
+
+    /// Release an established slot of `kind` because the connection closed.
+    pub fn on_closed(&self, kind: LimitKind) {
+        let mut counts = self.counts.lock().unwrap();
+        let entry = counts.entry(kind).or_default();
+        entry.established = entry.established.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_fails_once_established_reaches_cap() {
+        let limits = ConnectionLimits::new(1, 1, 1);
+        limits.on_dial_started(LimitKind::RelayedConnection);
+        limits.on_established(LimitKind::RelayedConnection);
+        assert!(limits.check(LimitKind::RelayedConnection).is_err());
+    }
+
+    #[test]
+    fn failed_dial_releases_pending_slot() {
+        let limits = ConnectionLimits::new(1, 1, 1);
+        limits.on_dial_started(LimitKind::PunchAttempt);
+        assert!(limits.check(LimitKind::PunchAttempt).is_err());
+
+        limits.on_dial_failed(LimitKind::PunchAttempt);
+        assert!(limits.check(LimitKind::PunchAttempt).is_ok());
+    }
+
+    #[test]
+    fn burst_of_failures_does_not_leak_slots() {
+        let limits = ConnectionLimits::new(2, 2, 2);
+        for _ in 0..10 {
+            limits.on_dial_started(LimitKind::RelayedConnection);
+            limits.on_dial_failed(LimitKind::RelayedConnection);
+        }
+        assert!(limits.check(LimitKind::RelayedConnection).is_ok());
+    }
+
+    #[test]
+    fn try_reserve_fails_once_pending_reaches_cap() {
+        let limits = ConnectionLimits::new(1, 1, 1);
+        assert!(limits.try_reserve(LimitKind::PunchAttempt).is_ok());
+        assert!(limits.try_reserve(LimitKind::PunchAttempt).is_err());
+    }
+
+    #[test]
+    fn closing_a_connection_frees_its_established_slot() {
+        let limits = ConnectionLimits::new(1, 1, 1);
+        limits.on_dial_started(LimitKind::DirectConnection);
+        limits.on_established(LimitKind::DirectConnection);
+        assert!(limits.check(LimitKind::DirectConnection).is_err());
+
+        limits.on_closed(LimitKind::DirectConnection);
+        assert!(limits.check(LimitKind::DirectConnection).is_ok());
+    }
+}
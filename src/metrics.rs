@@ -0,0 +1,111 @@
+//! Tracks historical punch outcomes to inform future heuristics.
+
+use crate::punch::PunchOutcome;
+use libp2p::PeerId;
+use std::collections::HashMap;
+
+/// Per-relay tally of punch outcomes, used to judge which relays produce
+/// punchable paths versus which merely yield `ConnectionReversed` partial wins.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RelayOutcomeCounts {
+    pub no_connection: u64,
+    pub no_stream: u64,
+    pub connection_reversed: u64,
+    pub cancelled: u64,
+    pub failed: u64,
+    pub direct: u64,
+    pub unknown: u64,
+}
+
+impl RelayOutcomeCounts {
+    fn record(&mut self, outcome: PunchOutcome) {
+        match outcome {
+            PunchOutcome::NoConnection => self.no_connection += 1,
+            PunchOutcome::NoStream => self.no_stream += 1,
+            PunchOutcome::ConnectionReversed => self.connection_reversed += 1,
+            PunchOutcome::Cancelled => self.cancelled += 1,
+            PunchOutcome::Failed => self.failed += 1,
+            PunchOutcome::Direct => self.direct += 1,
+            PunchOutcome::Unknown => self.unknown += 1,
+        }
+    }
+
+    /// Total number of outcomes recorded for this relay.
+    pub fn total(&self) -> u64 {
+        self.no_connection
+            + self.no_stream
+            + self.connection_reversed
+            + self.cancelled
+            + self.failed
+            + self.direct
+            + self.unknown
+    }
+
+    /// `Direct` is an outright success: the DCUtR stream opened and a
+    /// coordinated dial round established a direct connection.
+    pub fn full_successes(&self) -> u64 {
+        self.direct
+    }
+
+    /// `ConnectionReversed` is treated as a partial success: the stream never
+    /// opened, but a direct connection still materialized.
+    pub fn partial_successes(&self) -> u64 {
+        self.connection_reversed
+    }
+}
+
+/// Aggregates punch outcomes per relay so the heuristics can tell a reliably
+/// punchable relay apart from one that only ever produces failures.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsCollector {
+    by_relay: HashMap<PeerId, RelayOutcomeCounts>,
+}
+
+impl MetricsCollector {
+    /// Create an empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the outcome of a punch attempt that went through `relay_peer_id`.
+    pub fn record(&mut self, relay_peer_id: PeerId, outcome: PunchOutcome) {
+        self.by_relay.entry(relay_peer_id).or_default().record(outcome);
+    }
+
+    /// Outcome counts observed for a given relay, if any attempts were recorded.
+    pub fn counts_for(&self, relay_peer_id: &PeerId) -> Option<&RelayOutcomeCounts> {
+        self.by_relay.get(relay_peer_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_outcomes_per_relay() {
+        let relay_a = PeerId::random();
+        let relay_b = PeerId::random();
+        let mut metrics = MetricsCollector::new();
+
+        metrics.record(relay_a, PunchOutcome::Failed);
+        metrics.record(relay_a, PunchOutcome::ConnectionReversed);
+        metrics.record(relay_a, PunchOutcome::Direct);
+        metrics.record(relay_b, PunchOutcome::NoConnection);
+
+        let counts_a = metrics.counts_for(&relay_a).unwrap();
+        assert_eq!(counts_a.failed, 1);
+        assert_eq!(counts_a.partial_successes(), 1);
+        assert_eq!(counts_a.full_successes(), 1);
+        assert_eq!(counts_a.total(), 3);
+
+        let counts_b = metrics.counts_for(&relay_b).unwrap();
+        assert_eq!(counts_b.no_connection, 1);
+    }
+
+    #[test]
+    fn unknown_relay_has_no_counts() {
+        let metrics = MetricsCollector::new();
+        assert!(metrics.counts_for(&PeerId::random()).is_none());
+    }
+}
@@ -0,0 +1,235 @@
+//! DCUtR hole-punch orchestration.
+//!
+//! The [`PunchOrchestrator`] drives a single hole-punch attempt: it waits for
+//! the `/libp2p/dcutr` stream to negotiate over a relayed connection, then
+//! coordinates simultaneous-dial rounds with the remote peer and classifies
+//! the terminal state as a [`PunchOutcome`].
+
+use crate::discovery::{quic_transport_for_relay, AntiAmplificationError};
+use crate::{Config, RelayHandle};
+use libp2p::Multiaddr;
+use std::time::{Duration, Instant};
+
+/// Timeout for negotiating the `/libp2p/dcutr` stream over a relayed connection.
+pub const DCUTR_STREAM_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The terminal state of a single DCUtR hole-punch attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PunchOutcome {
+    /// Could not reach the peer through any of the provided relay multiaddrs.
+    NoConnection,
+    /// Connected via relay, but the `/libp2p/dcutr` stream did not negotiate
+    /// within [`DCUTR_STREAM_TIMEOUT`].
+    NoStream,
+    /// The DCUtR stream never opened within the timeout, yet a direct
+    /// connection still materialized because the remote peer reversed the dial.
+    ConnectionReversed,
+    /// The attempt was aborted by the user or a shutdown, or the only
+    /// addresses available were QUIC-only and cannot be hole-punched.
+    Cancelled,
+    /// The stream opened and all coordinated simultaneous-dial rounds were
+    /// exhausted without producing a direct connection.
+    Failed,
+    /// Ordinary DCUtR success: the stream opened and a coordinated dial round
+    /// established a direct connection.
+    Direct,
+    /// The attempt ended in a state that doesn't fit the other variants.
+    Unknown,
+}
+
+/// Source of DCUtR progress events for a single punch attempt.
+///
+/// Abstracted away from the live libp2p swarm so the orchestrator's decision
+/// logic can be driven and tested independently of a real connection.
+pub trait PunchEvents {
+    /// Block until the `/libp2p/dcutr` stream negotiates or `deadline` passes.
+    /// Returns `true` if the stream opened in time.
+    fn wait_for_stream(&mut self, deadline: Instant) -> bool;
+
+    /// Run the coordinated simultaneous-dial rounds against `candidates`.
+    /// Returns `true` if a direct connection was established.
+    fn run_dial_rounds(&mut self, candidates: &[Multiaddr]) -> bool;
+
+    /// Whether a direct path has appeared for the peer outside of the normal
+    /// dial rounds (e.g. the remote side reversed the dial).
+    fn has_direct_path(&self) -> bool;
+
+    /// Whether the attempt was cancelled (shutdown, user abort, or QUIC-only
+    /// addresses that cannot be hole-punched).
+    fn is_cancelled(&self) -> bool;
+}
+
+/// Coordinates a DCUtR hole-punch attempt against a single peer.
+pub struct PunchOrchestrator {
+    config: Config,
+}
+
+impl PunchOrchestrator {
+    /// Create a new orchestrator using the manager's configuration.
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+
+    /// Attempt to hole-punch a peer reachable via `relay`, trying `candidates`
+    /// in order, and classify the result.
+    ///
+    /// Fails fast with [`AntiAmplificationError`] if the configured QUIC
+    /// anti-amplification multiplier is invalid, since the transport used for
+    /// the coordinated dial rounds can't be set up without it.
+    pub fn attempt(
+        &self,
+        relay: &RelayHandle,
+        candidates: &[Multiaddr],
+        events: &mut impl PunchEvents,
+    ) -> Result<PunchOutcome, AntiAmplificationError> {
+        quic_transport_for_relay(&self.config, relay)?;
+
+        if events.is_cancelled() {
+            return Ok(PunchOutcome::Cancelled);
+        }
+
+        if candidates.is_empty() {
+            return Ok(PunchOutcome::NoConnection);
+        }
+
+        let deadline = Instant::now() + DCUTR_STREAM_TIMEOUT;
+        if !events.wait_for_stream(deadline) {
+            return Ok(if events.has_direct_path() {
+                PunchOutcome::ConnectionReversed
+            } else {
+                PunchOutcome::NoStream
+            });
+        }
+
+        if events.run_dial_rounds(candidates) {
+            return Ok(PunchOutcome::Direct);
+        }
+
+        Ok(if events.has_direct_path() {
+            PunchOutcome::ConnectionReversed
+        } else {
+            PunchOutcome::Failed
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeEvents {
+        stream_opens: bool,
+        dial_rounds_succeed: bool,
+        direct_path: bool,
+        cancelled: bool,
+    }
+
+    impl PunchEvents for FakeEvents {
+        fn wait_for_stream(&mut self, _deadline: Instant) -> bool {
+            self.stream_opens
+        }
+
+        fn run_dial_rounds(&mut self, _candidates: &[Multiaddr]) -> bool {
+            self.dial_rounds_succeed
+        }
+
+        fn has_direct_path(&self) -> bool {
+            self.direct_path
+        }
+
+        fn is_cancelled(&self) -> bool {
+            self.cancelled
+        }
+    }
+
+    fn relay() -> RelayHandle {
+        RelayHandle {
+            peer_id: libp2p::PeerId::random(),
+            relay_peer_id: libp2p::PeerId::random(),
+            rtt_ms: 50,
+        }
+    }
+
+    #[test]
+    fn no_candidates_is_no_connection() {
+        let orchestrator = PunchOrchestrator::new(Config::default());
+        let mut events = FakeEvents {
+            stream_opens: false,
+            dial_rounds_succeed: false,
+            direct_path: false,
+            cancelled: false,
+        };
+        let outcome = orchestrator.attempt(&relay(), &[], &mut events).unwrap();
+        assert_eq!(outcome, PunchOutcome::NoConnection);
+    }
+
+    #[test]
+    fn timeout_without_direct_path_is_no_stream() {
+        let orchestrator = PunchOrchestrator::new(Config::default());
+        let addr: Multiaddr = "/ip4/127.0.0.1/udp/4001/quic-v1".parse().unwrap();
+        let mut events = FakeEvents {
+            stream_opens: false,
+            dial_rounds_succeed: false,
+            direct_path: false,
+            cancelled: false,
+        };
+        let outcome = orchestrator.attempt(&relay(), &[addr], &mut events).unwrap();
+        assert_eq!(outcome, PunchOutcome::NoStream);
+    }
+
+    #[test]
+    fn timeout_with_direct_path_is_connection_reversed() {
+        let orchestrator = PunchOrchestrator::new(Config::default());
+        let addr: Multiaddr = "/ip4/127.0.0.1/udp/4001/quic-v1".parse().unwrap();
+        let mut events = FakeEvents {
+            stream_opens: false,
+            dial_rounds_succeed: false,
+            direct_path: true,
+            cancelled: false,
+        };
+        let outcome = orchestrator.attempt(&relay(), &[addr], &mut events).unwrap();
+        assert_eq!(outcome, PunchOutcome::ConnectionReversed);
+    }
+
+    #[test]
+    fn exhausted_rounds_without_direct_path_is_failed() {
+        let orchestrator = PunchOrchestrator::new(Config::default());
+        let addr: Multiaddr = "/ip4/127.0.0.1/udp/4001/quic-v1".parse().unwrap();
+        let mut events = FakeEvents {
+            stream_opens: true,
+            dial_rounds_succeed: false,
+            direct_path: false,
+            cancelled: false,
+        };
+        let outcome = orchestrator.attempt(&relay(), &[addr], &mut events).unwrap();
+        assert_eq!(outcome, PunchOutcome::Failed);
+    }
+
+    #[test]
+    fn successful_dial_round_is_direct() {
+        let orchestrator = PunchOrchestrator::new(Config::default());
+        let addr: Multiaddr = "/ip4/127.0.0.1/udp/4001/quic-v1".parse().unwrap();
+        let mut events = FakeEvents {
+            stream_opens: true,
+            dial_rounds_succeed: true,
+            direct_path: false,
+            cancelled: false,
+        };
+        let outcome = orchestrator.attempt(&relay(), &[addr], &mut events).unwrap();
+        assert_eq!(outcome, PunchOutcome::Direct);
+    }
+
+    #[test]
+    fn cancelled_attempt_short_circuits() {
+        let orchestrator = PunchOrchestrator::new(Config::default());
+        let addr: Multiaddr = "/ip4/127.0.0.1/udp/4001/quic-v1".parse().unwrap();
+        let mut events = FakeEvents {
+            stream_opens: true,
+            dial_rounds_succeed: true,
+            direct_path: false,
+            cancelled: true,
+        };
+        let outcome = orchestrator.attempt(&relay(), &[addr], &mut events).unwrap();
+        assert_eq!(outcome, PunchOutcome::Cancelled);
+    }
+}
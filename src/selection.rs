@@ -0,0 +1,168 @@
+//! Tracks candidate relays for a peer and selects the active relayed path.
+//!
+//! Candidates are ranked by RTT so the lowest-RTT live relay is always the
+//! active path, with instant failover to the next candidate if it drops.
+
+use crate::{ActivePath, RelayHandle};
+use libp2p::{Multiaddr, PeerId};
+use std::collections::HashMap;
+
+/// A relay candidate for a peer, along with the multiaddrs DCUtR should try
+/// through it.
+#[derive(Debug, Clone)]
+pub struct RelayCandidate {
+    pub handle: RelayHandle,
+    pub multiaddrs: Vec<Multiaddr>,
+    pub live: bool,
+}
+
+/// Tracks ranked relay candidates per peer and selects the active relayed path.
+#[derive(Debug, Clone, Default)]
+pub struct PathSelection {
+    candidates: HashMap<PeerId, Vec<RelayCandidate>>,
+}
+
+impl PathSelection {
+    /// Create an empty selection table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the candidate set for `peer_id`, re-ranking it by RTT.
+    pub fn set_candidates(&mut self, peer_id: PeerId, mut candidates: Vec<RelayCandidate>) {
+        candidates.sort_by_key(|candidate| candidate.handle.rtt_ms);
+        self.candidates.insert(peer_id, candidates);
+    }
+
+    /// Mark a relay as dropped so selection instantly fails over to the
+    /// next-best live candidate.
+    pub fn mark_dropped(&mut self, peer_id: &PeerId, relay_peer_id: &PeerId) {
+        if let Some(candidates) = self.candidates.get_mut(peer_id) {
+            for candidate in candidates.iter_mut() {
+                if &candidate.handle.relay_peer_id == relay_peer_id {
+                    candidate.live = false;
+                }
+            }
+        }
+    }
+
+    /// The lowest-RTT live relay for `peer_id`, to use as the active relayed path.
+    pub fn active_relay(&self, peer_id: &PeerId) -> Option<ActivePath> {
+        self.candidates
+            .get(peer_id)?
+            .iter()
+            .find(|candidate| candidate.live)
+            .map(|candidate| ActivePath::Relay(candidate.handle.clone()))
+    }
+
+    /// All live candidate multiaddrs for `peer_id`, in RTT order, for the
+    /// punch orchestrator to try DCUtR against.
+    pub fn punch_candidates(&self, peer_id: &PeerId) -> Vec<Multiaddr> {
+        self.candidates
+            .get(peer_id)
+            .into_iter()
+            .flatten()
+            .filter(|candidate| candidate.live)
+            .flat_map(|candidate| candidate.multiaddrs.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(relay_peer_id: PeerId, rtt_ms: u64, addr: &str) -> RelayCandidate {
+        RelayCandidate {
+            handle: RelayHandle {
+                peer_id: PeerId::random(),
+                relay_peer_id,
+                rtt_ms,
+            },
+            multiaddrs: vec![addr.parse().unwrap()],
+            live: true,
+        }
+    }
+
+    #[test]
+    fn active_relay_is_lowest_rtt_live_candidate() {
+        let mut selection = PathSelection::new();
+        let peer_id = PeerId::random();
+        let fast = PeerId::random();
+        let slow = PeerId::random();
+
+        selection.set_candidates(
+            peer_id,
+            vec![
+                candidate(slow, 300, "/ip4/127.0.0.1/udp/4002/quic-v1"),
+                candidate(fast, 50, "/ip4/127.0.0.1/udp/4001/quic-v1"),
+            ],
+        );
+
+        let active = selection.active_relay(&peer_id).unwrap();
+        match active {
+            ActivePath::Relay(handle) => assert_eq!(handle.relay_peer_id, fast),
+            ActivePath::Direct(_) => panic!("expected a relayed path"),
+        }
+    }
+
+    #[test]
+    fn failover_skips_dropped_relay() {
+        let mut selection = PathSelection::new();
+        let peer_id = PeerId::random();
+        let fast = PeerId::random();
+        let slow = PeerId::random();
+
+        selection.set_candidates(
+            peer_id,
+            vec![
+                candidate(fast, 50, "/ip4/127.0.0.1/udp/4001/quic-v1"),
+                candidate(slow, 300, "/ip4/127.0.0.1/udp/4002/quic-v1"),
+            ],
+        );
+        selection.mark_dropped(&peer_id, &fast);
+
+        let active = selection.active_relay(&peer_id).unwrap();
+        match active {
+            ActivePath::Relay(handle) => assert_eq!(handle.relay_peer_id, slow),
+            ActivePath::Direct(_) => panic!("expected a relayed path"),
+        }
+    }
+
+    #[test]
+    fn punch_candidates_are_collected_in_rtt_order() {
+        let mut selection = PathSelection::new();
+        let peer_id = PeerId::random();
+        let fast = PeerId::random();
+        let slow = PeerId::random();
+
+        selection.set_candidates(
+            peer_id,
+            vec![
+                candidate(slow, 300, "/ip4/127.0.0.1/udp/4002/quic-v1"),
+                candidate(fast, 50, "/ip4/127.0.0.1/udp/4001/quic-v1"),
+            ],
+        );
+
+        let addrs = selection.punch_candidates(&peer_id);
+        assert_eq!(addrs.len(), 2);
+        assert_eq!(addrs[0].to_string(), "/ip4/127.0.0.1/udp/4001/quic-v1");
+        assert_eq!(addrs[1].to_string(), "/ip4/127.0.0.1/udp/4002/quic-v1");
+    }
+
+    #[test]
+    fn no_live_candidates_means_no_active_relay() {
+        let mut selection = PathSelection::new();
+        let peer_id = PeerId::random();
+        let relay_peer_id = PeerId::random();
+
+        selection.set_candidates(
+            peer_id,
+            vec![candidate(relay_peer_id, 50, "/ip4/127.0.0.1/udp/4001/quic-v1")],
+        );
+        selection.mark_dropped(&peer_id, &relay_peer_id);
+
+        assert!(selection.active_relay(&peer_id).is_none());
+        assert!(selection.punch_candidates(&peer_id).is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+//! Exports punch results to a remote collector so DCUtR performance can be
+//! measured across a fleet, mirroring common hole-punch collector designs.
+
+use crate::punch::PunchOutcome;
+use async_trait::async_trait;
+use libp2p::{Multiaddr, PeerId};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A single punch attempt, ready to ship to a telemetry collector.
+#[derive(Debug, Clone)]
+pub struct PunchReport {
+    pub local_peer_id: PeerId,
+    pub remote_peer_id: PeerId,
+    pub relay_peer_id: PeerId,
+    pub candidates: Vec<Multiaddr>,
+    pub outcome: PunchOutcome,
+    pub relay_rtt_ms: u64,
+    pub direct_rtt_ms: Option<u64>,
+    pub time_to_stream: Option<Duration>,
+    pub time_to_direct: Option<Duration>,
+}
+
+/// Destination for punch reports.
+///
+/// Implementations must never block the connection path: slow or
+/// unreachable collectors should be buffered against and dropped, not
+/// awaited inline.
+#[async_trait]
+pub trait TelemetrySink: Send + Sync {
+    async fn report(&self, event: PunchReport);
+}
+
+/// Keeps reports in memory. Useful for tests and local debugging; not
+/// suitable for production fleets since nothing is ever flushed off-box.
+#[derive(Debug, Default)]
+pub struct InMemorySink {
+    reports: Mutex<Vec<PunchReport>>,
+}
+
+impl InMemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot of everything reported so far.
+    pub fn reports(&self) -> Vec<PunchReport> {
+        self.reports.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl TelemetrySink for InMemorySink {
+    async fn report(&self, event: PunchReport) {
+        self.reports.lock().unwrap().push(event);
+    }
+}
+
+/// Configuration for [`BatchingNetworkSink`].
+#[derive(Debug, Clone)]
+pub struct NetworkSinkConfig {
+    /// Collector endpoint reports are flushed to.
+    pub endpoint: String,
+    /// Maximum number of reports sent in a single flush.
+    pub batch_size: usize,
+    /// How many times to retry a failed batch before dropping it.
+    pub max_retries: u32,
+    /// Oldest-first cap on buffered reports; once exceeded, the oldest
+    /// report is dropped so a stalled collector can't grow memory unbounded.
+    pub max_buffered: usize,
+}
+
+impl Default for NetworkSinkConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://telemetry.example.invalid/v1/punch-reports".to_string(),
+            batch_size: 50,
+            max_retries: 3,
+            max_buffered: 1000,
+        }
+    }
+}
+
+/// Error returned by a telemetry transport attempt.
+#[derive(Debug, Clone)]
+pub struct TransportError(pub String);
+
+/// Sends a single batch of reports to `endpoint` over the wire.
+///
+/// Pluggable so [`BatchingNetworkSink`]'s batching and retry logic can be
+/// exercised against a fake in tests, and so an embedding application can
+/// supply whatever HTTP/gRPC client it already uses to reach its collector.
+#[async_trait]
+pub trait ReportTransport: Send + Sync {
+    async fn send(&self, endpoint: &str, batch: &[PunchReport]) -> Result<(), TransportError>;
+}
+
+/// Buffers reports and flushes them in batches over a configurable endpoint,
+/// with bounded buffering and retry so a slow or unreachable collector can
+/// never block the connection path.
+pub struct BatchingNetworkSink {
+    config: NetworkSinkConfig,
+    transport: Arc<dyn ReportTransport>,
+    buffer: Mutex<Vec<PunchReport>>,
+}
+
+impl BatchingNetworkSink {
+    pub fn new(config: NetworkSinkConfig, transport: Arc<dyn ReportTransport>) -> Self {
+        Self {
+            config,
+            transport,
+            buffer: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Number of reports currently buffered, awaiting flush.
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.lock().unwrap().len()
+    }
+
+    /// Drain the buffer and send it to the collector in `batch_size` chunks,
+    /// retrying each chunk up to `max_retries` times before dropping it.
+    pub async fn flush(&self) {
+        let batches: Vec<Vec<PunchReport>> = {
+            let mut buffer = self.buffer.lock().unwrap();
+            buffer
+                .drain(..)
+                .collect::<Vec<_>>()
+                .chunks(self.config.batch_size.max(1))
+                .map(|chunk| chunk.to_vec())
+                .collect()
+        };
+
+        for batch in batches {
+            self.send_with_retry(batch).await;
+        }
+    }
+
+    async fn send_with_retry(&self, batch: Vec<PunchReport>) {
+        let mut attempt = 0;
+        loop {
+            match self.send_batch(&batch).await {
+                Ok(()) => return,
+                Err(_) if attempt < self.config.max_retries => {
+                    attempt += 1;
+                }
+                Err(_) => return,
+            }
+        }
+    }
+
+    async fn send_batch(&self, batch: &[PunchReport]) -> Result<(), TransportError> {
+        self.transport.send(&self.config.endpoint, batch).await
+    }
+}
+
+#[async_trait]
+impl TelemetrySink for BatchingNetworkSink {
+    async fn report(&self, event: PunchReport) {
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.len() >= self.config.max_buffered {
+            buffer.remove(0);
+        }
+        buffer.push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::punch::PunchOutcome;
+
+    fn report(outcome: PunchOutcome) -> PunchReport {
+        PunchReport {
+            local_peer_id: PeerId::random(),
+            remote_peer_id: PeerId::random(),
+            relay_peer_id: PeerId::random(),
+            candidates: Vec::new(),
+            outcome,
+            relay_rtt_ms: 50,
+            direct_rtt_ms: None,
+            time_to_stream: None,
+            time_to_direct: None,
+        }
+    }
+
+    /// A [`ReportTransport`] that fails `fail_times` sends before succeeding,
+    /// counting every call it receives.
+    struct FakeTransport {
+        fail_times: Mutex<u32>,
+        calls: Mutex<u32>,
+    }
+
+    impl FakeTransport {
+        fn new(fail_times: u32) -> Self {
+            Self {
+                fail_times: Mutex::new(fail_times),
+                calls: Mutex::new(0),
+            }
+        }
+
+        fn calls(&self) -> u32 {
+            *self.calls.lock().unwrap()
+        }
+
+        fn always_succeeds() -> Arc<Self> {
+            Arc::new(Self::new(0))
+        }
+    }
+
+    #[async_trait]
+    impl ReportTransport for FakeTransport {
+        async fn send(&self, _endpoint: &str, _batch: &[PunchReport]) -> Result<(), TransportError> {
+            *self.calls.lock().unwrap() += 1;
+            let mut remaining = self.fail_times.lock().unwrap();
+            if *remaining > 0 {
+                *remaining -= 1;
+                Err(TransportError("simulated transport failure".to_string()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn in_memory_sink_collects_reports() {
+        let sink = InMemorySink::new();
+        sink.report(report(PunchOutcome::Failed)).await;
+        sink.report(report(PunchOutcome::ConnectionReversed)).await;
+        assert_eq!(sink.reports().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn batching_sink_drops_oldest_when_buffer_is_full() {
+        let sink = BatchingNetworkSink::new(
+            NetworkSinkConfig {
+                max_buffered: 2,
+                ..Default::default()
+            },
+            FakeTransport::always_succeeds(),
+        );
+
+        sink.report(report(PunchOutcome::Failed)).await;
+        sink.report(report(PunchOutcome::Failed)).await;
+        sink.report(report(PunchOutcome::Failed)).await;
+
+        assert_eq!(sink.buffered_len(), 2);
+    }
+
+    #[tokio::test]
+    async fn flush_drains_the_buffer() {
+        let sink = BatchingNetworkSink::new(NetworkSinkConfig::default(), FakeTransport::always_succeeds());
+        sink.report(report(PunchOutcome::Failed)).await;
+        sink.flush().await;
+        assert_eq!(sink.buffered_len(), 0);
+    }
+
+    #[tokio::test]
+    async fn retries_until_transport_succeeds() {
+        let transport = Arc::new(FakeTransport::new(2));
+        let sink = BatchingNetworkSink::new(NetworkSinkConfig::default(), transport.clone());
+
+        sink.report(report(PunchOutcome::Failed)).await;
+        sink.flush().await;
+
+        assert_eq!(transport.calls(), 3);
+        assert_eq!(sink.buffered_len(), 0);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let transport = Arc::new(FakeTransport::new(100));
+        let sink = BatchingNetworkSink::new(
+            NetworkSinkConfig {
+                max_retries: 3,
+                ..Default::default()
+            },
+            transport.clone(),
+        );
+
+        sink.report(report(PunchOutcome::Failed)).await;
+        sink.flush().await;
+
+        assert_eq!(transport.calls(), 4);
+    }
+}